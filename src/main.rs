@@ -16,6 +16,47 @@ use tokio::{
 use tracing::info;
 use url::Url;
 
+/// Writes `contents` to `dest` atomically by writing to a temp file in the same
+/// directory, flushing it fully, and renaming it over `dest`.
+///
+/// Writing to a temp file first and renaming it into place means a crash or kill
+/// mid-write can never leave `dest` truncated: the rename is a single, atomic
+/// filesystem operation, and keeping the temp file on the same filesystem as
+/// `dest` guarantees the rename can't fall back to a non-atomic copy.
+async fn write_atomically(dest: &PathBuf, contents: &str) -> anyhow::Result<()> {
+    let dir = dest
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+
+    let temp_path = dir.join(format!(
+        ".{}.tmp-{}",
+        dest.file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("tatum-out"),
+        std::process::id()
+    ));
+
+    let temp_file = File::create(&temp_path).await?;
+    let mut temp_file = BufWriter::new(temp_file);
+
+    temp_file.write_all(contents.as_bytes()).await?;
+    temp_file.flush().await?;
+    temp_file.get_ref().sync_all().await?;
+    drop(temp_file);
+
+    // `rename` isn't guaranteed to replace an existing destination on Windows, so
+    // remove it first there; elsewhere `rename` atomically replaces the target.
+    #[cfg(windows)]
+    if dest.exists() {
+        tokio::fs::remove_file(dest).await?;
+    }
+
+    tokio::fs::rename(&temp_path, dest).await?;
+
+    Ok(())
+}
+
 /// Simple program to greet a person
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -38,6 +79,12 @@ enum Args {
         /// Specify a file path to open in a browser.
         #[arg(short, long)]
         open: Option<PathBuf>,
+
+        /// Serve an entire directory of notes rather than a single file,
+        /// exposing a `/tree` endpoint the page uses to render a navigable
+        /// sidebar. Defaults to `open` when that path is itself a directory.
+        #[arg(short, long)]
+        root: Option<PathBuf>,
     },
     Render {
         /// The location of the Markdown file to render.
@@ -60,12 +107,15 @@ async fn main() {
             port,
             address,
             open,
+            root,
         } => {
             if !quiet {
                 tracing_subscriber::fmt::init();
             }
 
-            let app = construct_router();
+            let root = root.or_else(|| open.clone().filter(|path| path.is_dir()));
+
+            let app = construct_router(root);
 
             let listener = tokio::net::TcpListener::bind((address, port))
                 .await
@@ -78,12 +128,18 @@ async fn main() {
             }
 
             if let Some(url) = open {
-                open::that(format!(
-                    "http://{}?path={}",
-                    listener.local_addr().unwrap(),
-                    url.as_os_str().to_str().unwrap()
-                ))
-                .unwrap();
+                if url.is_dir() {
+                    // Directory mode has no single file to render; land on the
+                    // bare page so the sidebar built from `/tree` takes over.
+                    open::that(format!("http://{}", listener.local_addr().unwrap())).unwrap();
+                } else {
+                    open::that(format!(
+                        "http://{}?path={}",
+                        listener.local_addr().unwrap(),
+                        url.as_os_str().to_str().unwrap()
+                    ))
+                    .unwrap();
+                }
             }
 
             axum::serve(listener, app).await.unwrap();
@@ -118,17 +174,65 @@ async fn main() {
                 }
             }
 
-            let out_file = File::create(out_file)
-                .await
-                .expect("Unable to open out_file.");
-            let mut out_file = BufWriter::new(out_file);
-
-            out_file
-                .write_all(html.as_bytes())
+            write_atomically(&out_file, &html)
                 .await
                 .expect("Unable to write to file.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            out_file.flush().await.expect("Unable to write to file.");
+    async fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tatum-write-atomically-{name}-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_write_atomically_creates_file_with_contents() {
+        let dir = temp_dir("create").await;
+        let dest = dir.join("out.html");
+
+        write_atomically(&dest, "<html></html>").await.unwrap();
+
+        let contents = tokio::fs::read_to_string(&dest).await.unwrap();
+        assert_eq!(contents, "<html></html>");
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_atomically_overwrites_existing_file() {
+        let dir = temp_dir("overwrite").await;
+        let dest = dir.join("out.html");
+        tokio::fs::write(&dest, "old").await.unwrap();
+
+        write_atomically(&dest, "new").await.unwrap();
+
+        let contents = tokio::fs::read_to_string(&dest).await.unwrap();
+        assert_eq!(contents, "new");
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_atomically_leaves_no_temp_file_behind() {
+        let dir = temp_dir("cleanup").await;
+        let dest = dir.join("out.html");
+
+        write_atomically(&dest, "contents").await.unwrap();
+
+        let mut read_dir = tokio::fs::read_dir(&dir).await.unwrap();
+        let mut names = Vec::new();
+        while let Some(entry) = read_dir.next_entry().await.unwrap() {
+            names.push(entry.file_name());
         }
+
+        assert_eq!(names, vec![dest.file_name().unwrap().to_owned()]);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
     }
 }
@@ -16,6 +16,13 @@ fn data_url(data: &[u8], mime_type: &str) -> String {
     format!("data:{};base64,{encoded}", mime_type)
 }
 
+/// Percent-encodes `value` for use as a single query string value, so a path
+/// containing a space, `&`, `#`, or `%` doesn't truncate or corrupt the
+/// query string it's embedded in.
+fn encode_query_value(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
 /// Gets the file at a specified path, loads it, and converts it to a base64-encoded data URL
 async fn path_to_data_url(path: impl AsRef<Path>) -> anyhow::Result<String> {
     let file = read(&path).await?;
@@ -68,10 +75,26 @@ pub async fn render_doc(path: impl AsRef<Path>, use_websocket: bool) -> anyhow::
             if dest_url.parse::<Url>().is_ok() {
                 continue;
             } else if let Ok(image_path) = dest_url.parse::<PathBuf>() {
-                *dest_url = path_to_data_url(image_path.resolve_in(&path))
-                    .await
-                    .unwrap_or(generate_message_data_url("Disk error.", "red"))
+                let resolved = image_path.resolve_in(&path);
+
+                if use_websocket {
+                    // Serving over the websocket means we have a live server to hand
+                    // the browser a real URL, so let it fetch (and cache) the asset
+                    // itself instead of inflating it into an inline data URL.
+                    let resolved: PathBuf = resolved.as_ref().to_path_buf();
+                    let asset_path =
+                        get_relative_path_under_cwd(resolved.clone()).unwrap_or(resolved);
+                    *dest_url = format!(
+                        "/asset?path={}",
+                        encode_query_value(asset_path.to_str().unwrap_or_default())
+                    )
                     .into()
+                } else {
+                    *dest_url = path_to_data_url(resolved)
+                        .await
+                        .unwrap_or(generate_message_data_url("Disk error.", "red"))
+                        .into()
+                }
                 // Tag::Link
             } else {
                 *dest_url = generate_message_data_url("Unable to parse image path.", "red").into();
@@ -104,7 +127,11 @@ pub async fn render_doc(path: impl AsRef<Path>, use_websocket: bool) -> anyhow::
                             Some(path) => path,
                             None => file_path,
                         };
-                        *dest_url = format!("/?path={}", file_path.to_str().unwrap()).into()
+                        *dest_url = format!(
+                            "/?path={}",
+                            encode_query_value(file_path.to_str().unwrap())
+                        )
+                        .into()
                     }
                 }
             }
@@ -138,7 +165,7 @@ pub async fn render_doc(path: impl AsRef<Path>, use_websocket: bool) -> anyhow::
 /// let path = get_relative_or_absolute_path(file_path).unwrap();
 /// println!("{:?}", path); // Outputs the relative or absolute path to "file.txt"
 /// ```
-fn get_relative_path_under_cwd(file_path: PathBuf) -> Option<PathBuf> {
+pub(crate) fn get_relative_path_under_cwd(file_path: PathBuf) -> Option<PathBuf> {
     if let Ok(current_dir) = std::env::current_dir() {
         if is_child_path(current_dir, file_path.clone()) {
             truncate_cwd(&file_path)
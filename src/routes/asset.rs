@@ -0,0 +1,280 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use resolve_path::PathResolveExt;
+use serde::Deserialize;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
+use tokio_util::io::ReaderStream;
+use tracing::info;
+
+use super::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct AssetParams {
+    path: PathBuf,
+}
+
+/// A single, inclusive byte range to serve, or a marker that the requested
+/// range can't be satisfied against the file's actual length.
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    Full,
+    Satisfiable(u64, u64),
+    Unsatisfiable,
+}
+
+/// Serves a file off disk as a real HTTP resource, honoring conditional
+/// (`If-Modified-Since`) and `Range` requests so browsers can cache images and
+/// seek into large assets instead of pulling the whole thing into memory.
+pub async fn asset(
+    State(state): State<AppState>,
+    Query(AssetParams { path }): Query<AssetParams>,
+    headers: HeaderMap,
+) -> Response {
+    let path = path.resolve();
+
+    if !state.allows(&path) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    info!("Serving asset {}", path.to_string_lossy());
+
+    let metadata = match tokio::fs::metadata(&path).await {
+        Ok(metadata) if metadata.is_file() => metadata,
+        // A directory (or anything else that isn't a regular file) passes
+        // `metadata()` fine, but reading it would fail with `EISDIR` after
+        // the headers (and a promised `Content-Length`) were already sent.
+        // Report it as not found up front instead.
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    let file_len = metadata.len();
+    let last_modified = metadata.modified().unwrap_or_else(|_| SystemTime::now());
+
+    if is_not_modified(&headers, last_modified) {
+        return StatusCode::NOT_MODIFIED.into_response();
+    }
+
+    let mime = mime_guess::from_path(&path).first_or_octet_stream();
+
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| parse_range(value, file_len))
+        .unwrap_or(ByteRange::Full);
+
+    let (start, end) = match range {
+        ByteRange::Unsatisfiable => {
+            return Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{file_len}"))
+                .body(Body::empty())
+                .unwrap();
+        }
+        ByteRange::Satisfiable(start, end) => (start, end),
+        ByteRange::Full => (0, file_len.saturating_sub(1)),
+    };
+
+    let mut file = match File::open(&path).await {
+        Ok(file) => file,
+        Err(_) => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    if start > 0 && file.seek(SeekFrom::Start(start)).await.is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    let body_len = range_body_len(file_len, start, end);
+    let stream = ReaderStream::new(file.take(body_len));
+
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, mime.as_ref())
+        .header(header::CONTENT_LENGTH, body_len)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::LAST_MODIFIED, httpdate::fmt_http_date(last_modified));
+
+    response = if matches!(range, ByteRange::Satisfiable(..)) {
+        response
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{file_len}"))
+    } else {
+        response.status(StatusCode::OK)
+    };
+
+    response.body(Body::from_stream(stream)).unwrap()
+}
+
+/// Returns the number of bytes to read for the inclusive range `start..=end`
+/// of a file of length `file_len`. A zero-length file has nothing to read
+/// regardless of what `start`/`end` fell out to, which otherwise floors to
+/// `(0, 0)` and declares a `Content-Length: 1` for a stream that yields zero
+/// bytes.
+fn range_body_len(file_len: u64, start: u64, end: u64) -> u64 {
+    if file_len == 0 {
+        0
+    } else {
+        end.saturating_sub(start) + 1
+    }
+}
+
+fn is_not_modified(headers: &HeaderMap, last_modified: SystemTime) -> bool {
+    let Some(since) = headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| httpdate::parse_http_date(value).ok())
+    else {
+        return false;
+    };
+
+    // HTTP dates only carry second resolution, so round the file's mtime down
+    // to match before comparing.
+    let last_modified_secs = last_modified
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let since_secs = since
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    last_modified_secs <= since_secs
+}
+
+/// Parses a `Range: bytes=...` header against a file of length `file_len`.
+/// Only the first range of a (possibly multi-range) request is honored, end
+/// offsets are clamped to the file's actual length, and any out-of-bounds or
+/// malformed range is reported as unsatisfiable. A header that doesn't start
+/// with `bytes=` is ignored and the full file is served.
+fn parse_range(header: &str, file_len: u64) -> ByteRange {
+    let Some(spec) = header.strip_prefix("bytes=") else {
+        return ByteRange::Full;
+    };
+
+    let Some(spec) = spec.split(',').next() else {
+        return ByteRange::Full;
+    };
+
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Unsatisfiable;
+    };
+
+    if start.is_empty() {
+        // Suffix range: the last `end` bytes of the file.
+        let Ok(suffix_len) = end.parse::<u64>() else {
+            return ByteRange::Unsatisfiable;
+        };
+
+        if suffix_len == 0 || file_len == 0 {
+            return ByteRange::Unsatisfiable;
+        }
+
+        return ByteRange::Satisfiable(file_len.saturating_sub(suffix_len), file_len - 1);
+    }
+
+    let Ok(start) = start.parse::<u64>() else {
+        return ByteRange::Unsatisfiable;
+    };
+
+    if start >= file_len {
+        return ByteRange::Unsatisfiable;
+    }
+
+    let end = if end.is_empty() {
+        file_len - 1
+    } else {
+        match end.parse::<u64>() {
+            Ok(end) => end.min(file_len - 1),
+            Err(_) => return ByteRange::Unsatisfiable,
+        }
+    };
+
+    if end < start {
+        return ByteRange::Unsatisfiable;
+    }
+
+    ByteRange::Satisfiable(start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range_non_bytes_unit_is_full() {
+        assert_eq!(parse_range("not-a-range-header", 1000), ByteRange::Full);
+    }
+
+    #[test]
+    fn test_parse_range_start_to_end() {
+        assert_eq!(parse_range("bytes=0-499", 1000), ByteRange::Satisfiable(0, 499));
+    }
+
+    #[test]
+    fn test_parse_range_open_ended() {
+        assert_eq!(parse_range("bytes=500-", 1000), ByteRange::Satisfiable(500, 999));
+    }
+
+    #[test]
+    fn test_parse_range_suffix() {
+        assert_eq!(parse_range("bytes=-500", 1000), ByteRange::Satisfiable(500, 999));
+    }
+
+    #[test]
+    fn test_parse_range_clamps_end_to_file_length() {
+        assert_eq!(parse_range("bytes=0-99999", 1000), ByteRange::Satisfiable(0, 999));
+    }
+
+    #[test]
+    fn test_parse_range_start_beyond_file_length_is_unsatisfiable() {
+        assert_eq!(parse_range("bytes=1000-", 1000), ByteRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_parse_range_out_of_order_is_unsatisfiable() {
+        assert_eq!(parse_range("bytes=500-100", 1000), ByteRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_parse_range_zero_length_suffix_is_unsatisfiable() {
+        assert_eq!(parse_range("bytes=-0", 1000), ByteRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_parse_range_against_empty_file_is_unsatisfiable() {
+        assert_eq!(parse_range("bytes=0-10", 0), ByteRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_parse_range_malformed_start_is_unsatisfiable() {
+        assert_eq!(parse_range("bytes=abc-10", 1000), ByteRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_parse_range_only_honors_first_of_multiple_ranges() {
+        assert_eq!(parse_range("bytes=0-99,200-299", 1000), ByteRange::Satisfiable(0, 99));
+    }
+
+    #[test]
+    fn test_range_body_len_normal() {
+        assert_eq!(range_body_len(1000, 0, 499), 500);
+    }
+
+    #[test]
+    fn test_range_body_len_single_byte() {
+        assert_eq!(range_body_len(1000, 999, 999), 1);
+    }
+
+    #[test]
+    fn test_range_body_len_empty_file_is_zero_even_for_the_full_range() {
+        // The "full range" of a zero-length file floors to (0, 0), which would
+        // otherwise be read as a single byte.
+        assert_eq!(range_body_len(0, 0, 0), 0);
+    }
+}
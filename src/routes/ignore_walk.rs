@@ -0,0 +1,222 @@
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::Gitignore;
+use tracing::warn;
+
+/// Pushes any `.gitignore`/`.ignore` matchers found directly in `dir` onto
+/// `stack` and returns how many were pushed, so the caller can pop the same
+/// count back off once it's done recursing into `dir`.
+pub fn push_ignore_matchers(dir: &Path, stack: &mut Vec<Gitignore>) -> usize {
+    let mut pushed = 0;
+    for name in [".gitignore", ".ignore"] {
+        let candidate = dir.join(name);
+        if candidate.is_file() {
+            let (matcher, err) = Gitignore::new(&candidate);
+            if let Some(err) = err {
+                warn!("Failed to parse {}: {err}", candidate.display());
+            }
+            stack.push(matcher);
+            pushed += 1;
+        }
+    }
+    pushed
+}
+
+/// Tests `path` against the ignore stack, checking the deepest (nearest)
+/// directory's matcher first so a more specific `.gitignore` can override a
+/// broader one higher up the tree, including via negation (`!pattern`).
+pub fn is_ignored(path: &Path, is_dir: bool, stack: &[Gitignore]) -> bool {
+    for matcher in stack.iter().rev() {
+        match matcher.matched(path, is_dir) {
+            ignore::Match::None => continue,
+            ignore::Match::Ignore(_) => return true,
+            ignore::Match::Whitelist(_) => return false,
+        }
+    }
+
+    false
+}
+
+/// Returns whether `path` resolves (through any symlinks) to somewhere under
+/// `root`, used to keep a recursive walk from escaping its configured root.
+pub fn resolves_under(root: &Path, path: &Path) -> bool {
+    path.canonicalize()
+        .map(|resolved| resolved.starts_with(root))
+        .unwrap_or(false)
+}
+
+/// A single filesystem entry immediately under a directory being walked,
+/// after symlink and ignore filtering has already been applied.
+pub struct WalkEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// Lists the direct children of `dir` that survive ignore and symlink
+/// filtering, sorted by file name. Symlinked directories are never followed
+/// at all (see below); a symlinked file that resolves outside of `root` is
+/// skipped too. `stack` is the accumulated `.gitignore`/`.ignore` matcher
+/// stack tested nearest-first; callers are expected to have already pushed
+/// `dir`'s own matchers onto it via [`push_ignore_matchers`] before calling
+/// this.
+pub fn list_dir_entries(dir: &Path, root: &Path, stack: &[Gitignore]) -> Vec<WalkEntry> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut dir_entries: Vec<_> = read_dir.filter_map(Result::ok).collect();
+    dir_entries.sort_by_key(|entry| entry.file_name());
+
+    let mut entries = Vec::new();
+
+    for entry in dir_entries {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        let path = entry.path();
+
+        // `DirEntry::file_type()` mirrors `lstat`, so a symlink is never
+        // reported as a directory even when it points at one. Resolve the
+        // real target type for symlinks below.
+        let is_symlink = file_type.is_symlink();
+
+        let is_dir = if is_symlink {
+            path.metadata().map(|metadata| metadata.is_dir()).unwrap_or(false)
+        } else {
+            file_type.is_dir()
+        };
+
+        if is_symlink {
+            if is_dir {
+                // Never recurse into a symlinked directory. A directory
+                // symlink loop (e.g. `notes/loop -> notes`, or a mutual
+                // A<->B pair) would otherwise resolve under `root` at every
+                // hop - each recursive call only resolves one hop of the
+                // symlink, so there's no OS-level ELOOP to stop it - and
+                // walk forever until the stack overflows.
+                continue;
+            }
+
+            // A symlinked file is still safe to list as long as it doesn't
+            // resolve outside of `root`.
+            if !resolves_under(root, &path) {
+                continue;
+            }
+        }
+
+        if is_ignored(&path, is_dir, stack) {
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        entries.push(WalkEntry { path, name, is_dir });
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ignore::gitignore::GitignoreBuilder;
+    use std::path::PathBuf;
+
+    fn gitignore(root: &Path, patterns: &[&str]) -> Gitignore {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in patterns {
+            builder.add_line(None, pattern).unwrap();
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_is_ignored_matches_pattern() {
+        let root = PathBuf::from("/notes");
+        let stack = vec![gitignore(&root, &["*.log"])];
+
+        assert!(is_ignored(&root.join("debug.log"), false, &stack));
+        assert!(!is_ignored(&root.join("note.md"), false, &stack));
+    }
+
+    #[test]
+    fn test_is_ignored_empty_stack_never_ignores() {
+        let root = PathBuf::from("/notes");
+
+        assert!(!is_ignored(&root.join("anything"), false, &[]));
+    }
+
+    #[test]
+    fn test_is_ignored_nearest_directory_wins() {
+        let root = PathBuf::from("/notes");
+        let stack = vec![
+            gitignore(&root, &["*.log"]),
+            gitignore(&root.join("sub"), &["!debug.log"]),
+        ];
+
+        // The deeper .gitignore's negation re-includes what the shallower one
+        // ignored, since it's tested first.
+        assert!(!is_ignored(&root.join("sub/debug.log"), false, &stack));
+        // A file not covered by the deeper .gitignore still falls through to
+        // the shallower one.
+        assert!(is_ignored(&root.join("sub/other.log"), false, &stack));
+    }
+
+    #[test]
+    fn test_resolves_under_accepts_root_itself() {
+        let root = std::env::temp_dir();
+
+        assert!(resolves_under(&root, &root));
+    }
+
+    #[test]
+    fn test_resolves_under_rejects_path_outside_root() {
+        let root = std::env::temp_dir();
+        let outside = PathBuf::from("/definitely/not/under/root");
+
+        assert!(!resolves_under(&root, &outside));
+    }
+
+    #[test]
+    fn test_list_dir_entries_filters_ignored_and_sorts_by_name() {
+        let dir = std::env::temp_dir().join(format!(
+            "tatum-list-dir-entries-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("b.md"), "").unwrap();
+        std::fs::write(dir.join("a.md"), "").unwrap();
+        std::fs::write(dir.join("debug.log"), "").unwrap();
+
+        let stack = vec![gitignore(&dir, &["*.log"])];
+        let entries = list_dir_entries(&dir, &dir, &stack);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["a.md", "b.md"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_list_dir_entries_never_follows_a_symlinked_directory() {
+        let dir = std::env::temp_dir().join(format!(
+            "tatum-list-dir-entries-symlink-loop-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("note.md"), "").unwrap();
+        // A directory symlink back to `dir` itself: every recursive call
+        // would resolve under `root` since it really is `dir`, so only
+        // refusing to follow symlinked directories at all stops the loop.
+        std::os::unix::fs::symlink(&dir, dir.join("loop")).unwrap();
+
+        let entries = list_dir_entries(&dir, &dir, &[]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["note.md"]);
+    }
+}
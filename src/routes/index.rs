@@ -1,19 +1,52 @@
 use std::path::PathBuf;
 
-use axum::{extract::Query, response::Html};
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
 use resolve_path::PathResolveExt;
 use serde::Deserialize;
 use tracing::info;
 
+use crate::page_template::PageTemplate;
 use crate::render::render_doc;
 
+use super::AppState;
+
 #[derive(Debug, Deserialize)]
 pub struct IndexParams {
-    path: PathBuf,
+    path: Option<PathBuf>,
 }
 
-pub async fn index(Query(IndexParams { path }): Query<IndexParams>) -> Html<String> {
+pub async fn index(
+    State(state): State<AppState>,
+    Query(IndexParams { path }): Query<IndexParams>,
+) -> Response {
+    let Some(path) = path else {
+        // Directory mode has no single file to open by default; render a
+        // sidebar-only shell and let the `/tree` listing pick a document.
+        // Outside of directory mode there's nothing to show without a path.
+        return if state.root.is_some() {
+            let template = PageTemplate {
+                title: "Tatum".to_string(),
+                body: String::new(),
+                use_websocket: false,
+            };
+            Html(template.render().unwrap()).into_response()
+        } else {
+            StatusCode::BAD_REQUEST.into_response()
+        };
+    };
+
+    let path = path.resolve();
+
+    if !state.allows(&path) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
     info!("Rendering document {}", path.to_string_lossy());
 
-    Html(render_doc(path.resolve(), true).await.unwrap())
+    Html(render_doc(&path, true).await.unwrap()).into_response()
 }
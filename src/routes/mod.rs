@@ -1,12 +1,50 @@
+use std::path::{Path, PathBuf};
+
 use axum::{routing::get, Router};
 
+mod asset;
+mod ignore_walk;
 mod index;
+mod search;
+mod tree;
 mod watch;
+use asset::asset;
 use index::index;
+use ignore_walk::resolves_under;
+use search::search;
+use tree::tree;
 use watch::watch;
 
-pub fn construct_router() -> Router {
+/// State shared across routes. `root` is set when Tatum is serving an entire
+/// notes directory rather than a single file, and backs the directory-wide
+/// `/tree` and `/search` routes.
+#[derive(Debug, Clone, Default)]
+pub struct AppState {
+    pub root: Option<PathBuf>,
+}
+
+impl AppState {
+    /// Returns whether `path` may be served. When a root directory is
+    /// configured, every route is scoped to it so `/asset`/`/?path=...`
+    /// can't be used to read arbitrary files the process has access to;
+    /// without a root (single-file mode) any path is allowed, same as
+    /// before directory mode existed.
+    pub fn allows(&self, path: &Path) -> bool {
+        match &self.root {
+            Some(root) => resolves_under(root, path),
+            None => true,
+        }
+    }
+}
+
+pub fn construct_router(root: Option<PathBuf>) -> Router {
+    let root = root.map(|root| root.canonicalize().unwrap_or(root));
+
     Router::new()
         .route("/", get(index))
         .route("/watch", get(watch))
+        .route("/asset", get(asset))
+        .route("/tree", get(tree))
+        .route("/search", get(search))
+        .with_state(AppState { root })
 }
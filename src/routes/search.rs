@@ -0,0 +1,310 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ignore::gitignore::Gitignore;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::render::get_relative_path_under_cwd;
+
+use super::ignore_walk::{list_dir_entries, push_ignore_matchers};
+use super::AppState;
+
+/// How many lines of context to collect on either side of a match by default.
+const DEFAULT_CONTEXT_LINES: usize = 2;
+/// How many matches to return when the caller doesn't pass `max_results`, so
+/// a plain search of a huge vault can't turn into an unbounded response. A
+/// `max_results` the caller does pass is used as given, larger or smaller.
+const DEFAULT_MAX_RESULTS: usize = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    query: String,
+    #[serde(default)]
+    regex: bool,
+    max_results: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    path: PathBuf,
+    line_number: usize,
+    line: String,
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+enum Matcher {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn is_match(&self, line: &str) -> bool {
+        match self {
+            Matcher::Substring(needle) => line.contains(needle.as_str()),
+            Matcher::Regex(re) => re.is_match(line),
+        }
+    }
+}
+
+/// Scans Markdown files under the configured root for lines matching `query`
+/// (a plain substring, or a regular expression when `regex=true`), returning
+/// matches with surrounding context so results can be fed straight back into
+/// `/?path=...`. Matches come back in walk order (files sorted by name within
+/// each directory, lines in file order); they are not scored or ranked. Honors the same `.gitignore`/`.ignore` rules as
+/// the `/tree` route, and caps at `max_results` so a huge vault stays
+/// responsive.
+pub async fn search(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> Json<Vec<SearchMatch>> {
+    let Some(root) = state.root else {
+        return Json(Vec::new());
+    };
+
+    let max_results = params.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+
+    let matcher = if params.regex {
+        match Regex::new(&params.query) {
+            Ok(re) => Matcher::Regex(re),
+            Err(_) => return Json(Vec::new()),
+        }
+    } else {
+        Matcher::Substring(params.query)
+    };
+
+    let matches = tokio::task::spawn_blocking(move || {
+        let root = root.canonicalize().unwrap_or(root);
+        let mut results = Vec::new();
+        search_dir(&root, &root, &mut Vec::new(), &matcher, max_results, &mut results);
+        results
+    })
+    .await
+    .unwrap_or_default();
+
+    Json(matches)
+}
+
+fn search_dir(
+    root: &Path,
+    dir: &Path,
+    stack: &mut Vec<Gitignore>,
+    matcher: &Matcher,
+    max_results: usize,
+    results: &mut Vec<SearchMatch>,
+) {
+    let pushed = push_ignore_matchers(dir, stack);
+
+    for entry in list_dir_entries(dir, root, stack) {
+        if results.len() >= max_results {
+            break;
+        }
+
+        if entry.is_dir {
+            search_dir(root, &entry.path, stack, matcher, max_results, results);
+        } else if entry.path.extension().and_then(|ext| ext.to_str()) == Some("md") {
+            search_file(&entry.path, matcher, max_results, results);
+        }
+    }
+
+    for _ in 0..pushed {
+        stack.pop();
+    }
+}
+
+/// A match still waiting on trailing context lines before it can be emitted.
+struct PendingMatch {
+    path: PathBuf,
+    line_number: usize,
+    line: String,
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+impl PendingMatch {
+    fn into_match(self) -> SearchMatch {
+        let path = get_relative_path_under_cwd(self.path.clone()).unwrap_or(self.path);
+
+        SearchMatch {
+            path,
+            line_number: self.line_number,
+            line: self.line,
+            before: self.before,
+            after: self.after,
+        }
+    }
+}
+
+/// Streams `path` line-by-line, matching each line against `matcher` without
+/// ever holding the whole file in memory. A sliding window keeps the last
+/// `DEFAULT_CONTEXT_LINES` lines around for `before` context; `after` context
+/// is filled in by continuing to read once a match is found.
+fn search_file(path: &Path, matcher: &Matcher, max_results: usize, results: &mut Vec<SearchMatch>) {
+    let Ok(file) = std::fs::File::open(path) else {
+        return;
+    };
+    let reader = BufReader::new(file);
+
+    let mut window: VecDeque<String> = VecDeque::with_capacity(DEFAULT_CONTEXT_LINES);
+    let mut pending: Vec<PendingMatch> = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        if results.len() >= max_results {
+            return;
+        }
+
+        let Ok(line) = line else {
+            break;
+        };
+
+        let mut still_pending = Vec::with_capacity(pending.len());
+        for mut pending_match in pending.drain(..) {
+            pending_match.after.push(line.clone());
+            if pending_match.after.len() >= DEFAULT_CONTEXT_LINES {
+                results.push(pending_match.into_match());
+            } else {
+                still_pending.push(pending_match);
+            }
+        }
+        pending = still_pending;
+
+        if results.len() >= max_results {
+            return;
+        }
+
+        if matcher.is_match(&line) {
+            pending.push(PendingMatch {
+                path: path.to_path_buf(),
+                line_number: index + 1,
+                line: line.clone(),
+                before: window.iter().cloned().collect(),
+                after: Vec::new(),
+            });
+        }
+
+        window.push_back(line);
+        if window.len() > DEFAULT_CONTEXT_LINES {
+            window.pop_front();
+        }
+    }
+
+    for pending_match in pending {
+        if results.len() >= max_results {
+            break;
+        }
+        results.push(pending_match.into_match());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write_temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("tatum-search-test-{name}-{}", std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_search_file_collects_before_and_after_context() {
+        let path = write_temp_file(
+            "context",
+            "line one\nline two\nMATCH here\nline four\nline five\nline six\n",
+        );
+
+        let matcher = Matcher::Substring("MATCH".to_string());
+        let mut results = Vec::new();
+        search_file(&path, &matcher, 10, &mut results);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line_number, 3);
+        assert_eq!(results[0].line, "MATCH here");
+        assert_eq!(results[0].before, vec!["line one", "line two"]);
+        assert_eq!(results[0].after, vec!["line four", "line five"]);
+    }
+
+    #[test]
+    fn test_search_file_truncates_context_at_file_boundaries() {
+        let path = write_temp_file("eof", "MATCH\nonly one line after\n");
+
+        let matcher = Matcher::Substring("MATCH".to_string());
+        let mut results = Vec::new();
+        search_file(&path, &matcher, 10, &mut results);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].before.is_empty());
+        assert_eq!(results[0].after, vec!["only one line after"]);
+    }
+
+    #[test]
+    fn test_search_file_respects_max_results() {
+        let path = write_temp_file("cap", "MATCH\nMATCH\nMATCH\n");
+
+        let matcher = Matcher::Substring("MATCH".to_string());
+        let mut results = Vec::new();
+        search_file(&path, &matcher, 1, &mut results);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_search_file_regex_matcher() {
+        let path = write_temp_file("regex", "foo123\nbar\nfoo456\n");
+
+        let matcher = Matcher::Regex(Regex::new(r"^foo\d+$").unwrap());
+        let mut results = Vec::new();
+        search_file(&path, &matcher, 10, &mut results);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].line, "foo123");
+        assert_eq!(results[1].line, "foo456");
+    }
+
+    #[test]
+    fn test_search_file_no_match_returns_empty() {
+        let path = write_temp_file("nomatch", "nothing to see here\n");
+
+        let matcher = Matcher::Substring("MATCH".to_string());
+        let mut results = Vec::new();
+        search_file(&path, &matcher, 10, &mut results);
+        fs::remove_file(&path).unwrap();
+
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_search_dir_does_not_recurse_into_a_symlinked_directory_loop() {
+        let dir = std::env::temp_dir().join(format!(
+            "tatum-search-dir-symlink-loop-test-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("note.md"), "MATCH\n").unwrap();
+        // A directory symlink back to `dir` itself would otherwise resolve
+        // under `root` at every hop and recurse until the stack overflows;
+        // `search_dir` relies on `list_dir_entries` refusing to follow it.
+        std::os::unix::fs::symlink(&dir, dir.join("loop")).unwrap();
+
+        let matcher = Matcher::Substring("MATCH".to_string());
+        let mut results = Vec::new();
+        search_dir(&dir, &dir, &mut Vec::new(), &matcher, 10, &mut results);
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line, "MATCH");
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+
+use axum::{extract::State, Json};
+use ignore::gitignore::Gitignore;
+use serde::Serialize;
+
+use super::ignore_walk::{list_dir_entries, push_ignore_matchers};
+use super::AppState;
+
+/// A single node in the directory tree served to the client for the sidebar.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TreeEntry {
+    File {
+        name: String,
+        path: PathBuf,
+    },
+    Directory {
+        name: String,
+        path: PathBuf,
+        children: Vec<TreeEntry>,
+    },
+}
+
+/// Walks the configured root directory and returns a nested listing of
+/// Markdown files and subfolders, skipping anything matched by a
+/// `.gitignore`/`.ignore` encountered along the way. Returns an empty list
+/// when Tatum isn't serving a directory.
+pub async fn tree(State(state): State<AppState>) -> Json<Vec<TreeEntry>> {
+    let Some(root) = state.root else {
+        return Json(Vec::new());
+    };
+
+    let entries = tokio::task::spawn_blocking(move || {
+        let root = root.canonicalize().unwrap_or(root);
+        walk_dir(&root, &root, &mut Vec::new())
+    })
+    .await
+    .unwrap_or_default();
+
+    Json(entries)
+}
+
+/// Recursively walks `dir`, maintaining a stack of `.gitignore`/`.ignore`
+/// matchers for the directories above it so a candidate path can be tested
+/// against the accumulated patterns nearest-first. Symlinked directories are
+/// skipped rather than followed, wherever they resolve.
+fn walk_dir(root: &Path, dir: &Path, stack: &mut Vec<Gitignore>) -> Vec<TreeEntry> {
+    let pushed = push_ignore_matchers(dir, stack);
+
+    let mut entries = Vec::new();
+
+    for entry in list_dir_entries(dir, root, stack) {
+        if entry.is_dir {
+            let children = walk_dir(root, &entry.path, stack);
+            if !children.is_empty() {
+                entries.push(TreeEntry::Directory {
+                    name: entry.name,
+                    path: entry.path,
+                    children,
+                });
+            }
+        } else if entry.path.extension().and_then(|ext| ext.to_str()) == Some("md") {
+            entries.push(TreeEntry::File {
+                name: entry.name,
+                path: entry.path,
+            });
+        }
+    }
+
+    for _ in 0..pushed {
+        stack.pop();
+    }
+
+    entries
+}
@@ -1,43 +1,236 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use axum::{
     extract::{
         ws::{Message, WebSocket},
-        Query, WebSocketUpgrade,
+        Query, State, WebSocketUpgrade,
     },
-    response::IntoResponse,
+    http::StatusCode,
+    response::{IntoResponse, Response},
 };
-use notify::{Config, RecommendedWatcher, Watcher};
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use resolve_path::PathResolveExt;
-use serde::Deserialize;
-use tracing::info;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::AppState;
+
+/// How long to wait for additional events before flushing a batch of changes to
+/// the client. Editors and filesystems tend to emit several notify events for
+/// what is conceptually a single save, so we coalesce anything arriving within
+/// this window into one message carrying the list of changes.
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(100);
 
 #[derive(Debug, Deserialize)]
 pub struct WatchParams {
     /// The path to watch for changes.
     path: PathBuf,
+
+    /// Whether to watch `path` and all of its subdirectories, rather than just
+    /// `path` itself.
+    #[serde(default)]
+    recursive: bool,
+}
+
+/// The kind of filesystem change that occurred, mirroring (a simplified view of)
+/// `notify::EventKind`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ChangeKind {
+    Create,
+    Modify,
+    Delete,
+    Rename,
+}
+
+/// A single, client-facing description of a filesystem change.
+#[derive(Debug, Serialize)]
+struct ChangeEvent {
+    kind: ChangeKind,
+    path: PathBuf,
+}
+
+impl ChangeKind {
+    fn from_notify(kind: &EventKind) -> Option<Self> {
+        match kind {
+            EventKind::Create(_) => Some(ChangeKind::Create),
+            EventKind::Modify(notify::event::ModifyKind::Name(_)) => Some(ChangeKind::Rename),
+            EventKind::Modify(_) => Some(ChangeKind::Modify),
+            EventKind::Remove(_) => Some(ChangeKind::Delete),
+            // Access events and anything else notify surfaces aren't interesting
+            // to a viewer watching for content changes.
+            _ => None,
+        }
+    }
 }
 
 /// A WebSocket endpoint that watches files for changes and notifies the client when they occur.
-pub async fn watch(ws: WebSocketUpgrade, Query(params): Query<WatchParams>) -> impl IntoResponse {
-    ws.on_upgrade(|socket| handle_ws(socket, params))
+/// Scoped to the configured root the same way `/asset` and `/` are, so in
+/// directory mode a client can't point the watcher at an arbitrary path
+/// outside it.
+pub async fn watch(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(params): Query<WatchParams>,
+) -> Response {
+    let path = params.path.resolve().into_owned();
+
+    if !state.allows(&path) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    ws.on_upgrade(move |socket| handle_ws(socket, path, params.recursive))
+        .into_response()
 }
 
-async fn handle_ws(mut socket: WebSocket, WatchParams { path }: WatchParams) {
+async fn handle_ws(mut socket: WebSocket, path: PathBuf, recursive: bool) {
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
 
-    let path = path.resolve();
+    let mut watcher = match RecommendedWatcher::new(
+        move |event: notify::Result<Event>| {
+            // The closure runs on notify's own thread; if the receiving task has
+            // already dropped its end there's nothing useful to do with the error.
+            let _ = tx.send(event);
+        },
+        Config::default(),
+    ) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            warn!("Failed to initialize file watcher: {err}");
+            return;
+        }
+    };
+
+    let recursive_mode = if recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+
+    if let Err(err) = watcher.watch(&path, recursive_mode) {
+        warn!("Failed to watch {}: {err}", path.to_string_lossy());
+        return;
+    }
+
+    while let Some(event) = rx.recv().await {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => {
+                warn!("Watcher error for {}: {err}", path.to_string_lossy());
+                continue;
+            }
+        };
+
+        let mut changes = match to_change_events(event) {
+            changes if changes.is_empty() => continue,
+            changes => changes,
+        };
+
+        // Drain any further events that arrive within the debounce window so a
+        // burst of notify events (common for a single save) becomes one message.
+        loop {
+            match tokio::time::timeout(DEBOUNCE_WINDOW, rx.recv()).await {
+                Ok(Some(Ok(event))) => changes.extend(to_change_events(event)),
+                Ok(Some(Err(err))) => {
+                    warn!("Watcher error for {}: {err}", path.to_string_lossy());
+                }
+                Ok(None) => break,
+                Err(_) => break,
+            }
+        }
+
+        for change in &changes {
+            info!(
+                "{:?} event for {}",
+                change.kind,
+                change.path.to_string_lossy()
+            );
+        }
 
-    let mut watcher =
-        RecommendedWatcher::new(move |event| tx.send(event).unwrap(), Config::default()).unwrap();
+        let message = match serde_json::to_string(&changes) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!("Failed to serialize change events: {err}");
+                continue;
+            }
+        };
 
-    watcher
-        .watch(&path, notify::RecursiveMode::NonRecursive)
-        .unwrap();
+        if socket.send(Message::Text(message)).await.is_err() {
+            // The client disconnected; end the task instead of unwrapping into a panic.
+            break;
+        }
+    }
+}
+
+fn to_change_events(event: Event) -> Vec<ChangeEvent> {
+    let Some(kind) = ChangeKind::from_notify(&event.kind) else {
+        return Vec::new();
+    };
+
+    event
+        .paths
+        .into_iter()
+        .map(|path| ChangeEvent { kind, path })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, DataChange, ModifyKind, RemoveKind, RenameMode};
+
+    fn event(kind: EventKind, paths: &[&str]) -> Event {
+        paths
+            .iter()
+            .fold(Event::new(kind), |event, path| event.add_path(PathBuf::from(path)))
+    }
+
+    #[test]
+    fn test_to_change_events_create() {
+        let changes = to_change_events(event(EventKind::Create(CreateKind::File), &["a.md"]));
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Create);
+        assert_eq!(changes[0].path, PathBuf::from("a.md"));
+    }
+
+    #[test]
+    fn test_to_change_events_modify() {
+        let changes = to_change_events(event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            &["a.md"],
+        ));
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Modify);
+    }
+
+    #[test]
+    fn test_to_change_events_rename_emits_one_event_per_path() {
+        let changes = to_change_events(event(
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+            &["old.md", "new.md"],
+        ));
+
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|change| change.kind == ChangeKind::Rename));
+        assert_eq!(changes[0].path, PathBuf::from("old.md"));
+        assert_eq!(changes[1].path, PathBuf::from("new.md"));
+    }
+
+    #[test]
+    fn test_to_change_events_delete() {
+        let changes = to_change_events(event(EventKind::Remove(RemoveKind::File), &["a.md"]));
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Delete);
+    }
 
-    while let Some(_event) = rx.recv().await {
-        info!("Received file change event for {}", path.to_string_lossy());
+    #[test]
+    fn test_to_change_events_ignores_uninteresting_kinds() {
+        let changes = to_change_events(event(EventKind::Access(AccessKind::Any), &["a.md"]));
 
-        socket.send(Message::Text("".to_string())).await.unwrap();
+        assert!(changes.is_empty());
     }
 }